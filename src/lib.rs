@@ -1,9 +1,15 @@
 extern crate num;
+extern crate rayon;
 
+pub mod palette;
+pub mod quantize;
 pub mod util;
 
 use num::complex::Complex64;
-use util::hsl_rgb;
+use palette::{HslPalette, Palette};
+use quantize::IndexedImage;
+use rayon::prelude::*;
+use util::{linear_to_srgb, srgb_to_linear};
 
 const DEFAULT_ZOOM: f64 = 0.003333333;
 
@@ -13,6 +19,21 @@ pub enum PixelFormat {
     BGRA8,
 }
 
+#[derive(Copy, Clone, Debug)]
+/// Selects the escape-time recurrence used by `Mandelbrot::calc_point`.
+pub enum FractalKind {
+    /// The classic `z = z*z + c` recurrence.
+    Mandelbrot,
+    /// `z = z*z + c_fixed`, seeded with the pixel coordinate instead of zero.
+    Julia { c: Complex64 },
+    /// `z = (|Re z| + i|Im z|)^2 + c`.
+    BurningShip,
+    /// The Mandelbar: `z = conj(z)^2 + c`.
+    Tricorn,
+    /// `z = z^power + c`.
+    Multibrot { power: u32 },
+}
+
 #[derive(Copy, Clone, Debug)]
 /// Configuration data for rendering a Mandelbrot fractal image
 pub struct Config {
@@ -22,10 +43,17 @@ pub struct Config {
     pub zoom: f64,
     pub iter: u16,
     pub pix: PixelFormat,
+    pub kind: FractalKind,
+    /// When `true`, pass 2 blends histogram colors in linear light instead of
+    /// directly in sRGB, which keeps gradients from banding or darkening in
+    /// the transitions. Defaults to `false` in both constructors.
+    pub linear_blend: bool,
 }
 
 impl Config {
-    /// Simple helper to construct configuration without exposing Complex64 implementation
+    /// Simple helper to construct configuration without exposing Complex64 implementation.
+    /// Defaults to the classic Mandelbrot recurrence; use [`Config::new_with_kind`] to
+    /// render one of the other escape-time fractal families.
     pub fn new(
         width: u32,
         height: u32,
@@ -34,6 +62,22 @@ impl Config {
         zoom: f64,
         iter: u16,
         pix: PixelFormat,
+    ) -> Config {
+        Config::new_with_kind(width, height, cx, cy, zoom, iter, pix, FractalKind::Mandelbrot)
+    }
+
+    /// Constructs configuration for a specific fractal family. See [`FractalKind`] for the
+    /// supported escape-time recurrences.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_with_kind(
+        width: u32,
+        height: u32,
+        cx: f64,
+        cy: f64,
+        zoom: f64,
+        iter: u16,
+        pix: PixelFormat,
+        kind: FractalKind,
     ) -> Config {
         Config {
             width,
@@ -42,6 +86,8 @@ impl Config {
             zoom,
             iter,
             pix,
+            kind,
+            linear_blend: false,
         }
     }
 }
@@ -52,6 +98,7 @@ pub struct Mandelbrot {
     hist: Vec<u32>,
     iter_total: i32,
     viewport: (Complex64, Complex64),
+    palette: Box<dyn Palette + Sync + Send>,
 }
 
 impl Mandelbrot {
@@ -64,7 +111,7 @@ impl Mandelbrot {
     //! extern crate num;
     //!
     //! use num::complex::Complex64;
-    //! use fractal_rs::{Config, Mandelbrot, PixelFormat};
+    //! use fractal_rs::{Config, FractalKind, Mandelbrot, PixelFormat};
     //!
     //! let center = Complex64 { re: 0.0, im: 0.0 };
     //! let config = Config {
@@ -74,6 +121,8 @@ impl Mandelbrot {
     //!     zoom: 0.5,
     //!     iter: 100,
     //!     pix: PixelFormat::RGBA8,
+    //!     kind: FractalKind::Mandelbrot,
+    //!     linear_blend: false,
     //! };
     //! let mut m = Mandelbrot::new(config);
     //! let img = m.render();
@@ -86,26 +135,16 @@ impl Mandelbrot {
     /// list. A viewport is constructed based on the configuration that factors
     /// in the width, height and zoom factor.
     pub fn new(config: Config) -> Mandelbrot {
-        let zoom = DEFAULT_ZOOM / config.zoom;
-        let viewport_height = config.height as f64 / 2.0 * zoom;
-        let viewport_width = config.width as f64 / 2.0 * zoom;
-        let viewport: (Complex64, Complex64) = (
-            Complex64::new(
-                config.center.re - viewport_width,
-                config.center.im + viewport_height,
-            ),
-            Complex64::new(
-                config.center.re + viewport_width,
-                config.center.im - viewport_height,
-            ),
-        );
         let hist = Vec::with_capacity(config.iter as usize);
-        Mandelbrot {
+        let mut m = Mandelbrot {
             config,
             hist,
             iter_total: 0,
-            viewport,
-        }
+            viewport: (Complex64::new(0.0, 0.0), Complex64::new(0.0, 0.0)),
+            palette: Box::new(HslPalette::default()),
+        };
+        m.recompute_viewport();
+        m
     }
 
     /// Gets the current viewport. The viewport is returned as a tuple (top-left, bottom-right)
@@ -113,29 +152,103 @@ impl Mandelbrot {
         (self.viewport.0, self.viewport.1)
     }
 
+    /// Replaces the palette used to color the histogram in pass 2 of `render`.
+    /// Defaults to the classic HSL sweep; see the [`palette`](crate::palette) module
+    /// for the other implementations.
+    pub fn set_palette(&mut self, palette: Box<dyn Palette + Sync + Send>) {
+        self.palette = palette;
+    }
+
+    /// Shifts `center` by `dx_pixels`/`dy_pixels` (in screen-pixel units, `y` growing
+    /// downward as in `render`) at the current zoom level, then recomputes the viewport.
+    /// Cheap compared to rebuilding a `Mandelbrot` from scratch, so an interactive
+    /// front-end can call it between renders.
+    pub fn pan(&mut self, dx_pixels: f64, dy_pixels: f64) {
+        let (x_step, y_step) = self.pixel_steps();
+        self.config.center.re += dx_pixels * x_step;
+        self.config.center.im -= dy_pixels * y_step;
+        self.recompute_viewport();
+    }
+
+    /// Multiplies the zoom factor by `factor`, keeping the complex point under
+    /// screen coordinate (`screen_x`, `screen_y`) fixed, so an interactive zoom
+    /// feels anchored to the cursor rather than to the center of the image.
+    pub fn zoom_at(&mut self, screen_x: u32, screen_y: u32, factor: f64) {
+        let before = self.pixel_to_complex(screen_x, screen_y);
+        self.config.zoom *= factor;
+        self.recompute_viewport();
+        let after = self.pixel_to_complex(screen_x, screen_y);
+        self.config.center += before - after;
+        self.recompute_viewport();
+    }
+
+    /// Recomputes `viewport` from the current `config.center`/`config.zoom`.
+    fn recompute_viewport(&mut self) {
+        let zoom = DEFAULT_ZOOM / self.config.zoom;
+        let viewport_height = self.config.height as f64 / 2.0 * zoom;
+        let viewport_width = self.config.width as f64 / 2.0 * zoom;
+        self.viewport = (
+            Complex64::new(
+                self.config.center.re - viewport_width,
+                self.config.center.im + viewport_height,
+            ),
+            Complex64::new(
+                self.config.center.re + viewport_width,
+                self.config.center.im - viewport_height,
+            ),
+        );
+    }
+
+    /// Per-pixel step in the complex plane for the current viewport.
+    fn pixel_steps(&self) -> (f64, f64) {
+        let x_step = (self.viewport.1.re - self.viewport.0.re).abs() / self.config.width as f64;
+        let y_step = (self.viewport.0.im - self.viewport.1.im).abs() / self.config.height as f64;
+        (x_step, y_step)
+    }
+
+    /// Maps a screen pixel coordinate to its complex sample point under the
+    /// current viewport, using the same mapping as `render`'s pass 1.
+    fn pixel_to_complex(&self, screen_x: u32, screen_y: u32) -> Complex64 {
+        let (x_step, y_step) = self.pixel_steps();
+        Complex64::new(
+            self.viewport.0.re + screen_x as f64 * x_step,
+            self.viewport.0.im - screen_y as f64 * y_step,
+        )
+    }
+
     /// Creates in-memory image data based on the current configuration.
     pub fn render(&mut self) -> Vec<u8> {
         let mut image = Vec::with_capacity((self.config.width * self.config.height * 4) as usize);
-        let mut image_iter = Vec::with_capacity((self.config.width * self.config.height) as usize);
-        // prepare the histogram
+        // reset histogram state so repeated render() calls don't accumulate
+        // stale counts from a previous pan/zoom
+        self.hist.clear();
+        self.iter_total = 0;
         for _ in 0..self.config.iter as usize {
             self.hist.push(0);
         }
-        let x_step = (self.viewport.1.re - self.viewport.0.re).abs() / self.config.width as f64;
-        let y_step = (self.viewport.0.im - self.viewport.1.im).abs() / self.config.height as f64;
-        // pass 1 - populate the raw pixel values and histogram
-        for y in 0..self.config.height {
-            for x in 0..self.config.width {
+        let (x_step, y_step) = self.pixel_steps();
+        let width = self.config.width;
+        // pass 1 - populate the raw pixel values in parallel, one independent
+        // sample per pixel since calc_point only reads &self
+        let mut image_iter = vec![0.0f64; (self.config.width * self.config.height) as usize];
+        image_iter
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(idx, iter)| {
+                let x = idx as u32 % width;
+                let y = idx as u32 / width;
                 let c = Complex64 {
                     re: self.viewport.0.re + (x as f64 * x_step),
                     im: self.viewport.0.im - (y as f64 * y_step),
                 };
-                let iter = self.calc_point(c);
-                if iter < self.config.iter as f64 {
-                    self.hist[iter.floor() as usize] += 1;
-                    self.iter_total += 1;
-                }
-                image_iter.push(iter);
+                *iter = self.calc_point(c);
+            });
+        // histogram accumulation is a sequential reduction over the finished
+        // buffer to avoid racing on self.hist/self.iter_total
+        for &iter in image_iter.iter() {
+            if iter < self.config.iter as f64 {
+                self.hist[iter.floor() as usize] += 1;
+                self.iter_total += 1;
             }
         }
         // pass 2 - normalize the histogram 0.0 - 1.0
@@ -150,23 +263,28 @@ impl Mandelbrot {
             for x in 0..self.config.width {
                 let idx = (y * self.config.width + x) as usize;
                 let m = image_iter[idx];
-                let mut hue: f32 = 0.0;
+                let mut t: f32 = 0.0;
                 // we allow direct float comparison since this is not comparing 2 calculated values
                 // but rather 2 previously stored floats
                 #[allow(clippy::float_cmp)]
                 if m != self.config.iter as f64 {
-                    hue = 360.0
-                        - Mandelbrot::linear_interpolation(
-                            hues[m.floor() as usize] as f64,
-                            hues[m.ceil() as usize] as f64,
-                            m % 1.0,
-                        ) * 360.0;
+                    t = Mandelbrot::linear_interpolation(
+                        hues[m.floor() as usize] as f64,
+                        hues[m.ceil() as usize] as f64,
+                        m % 1.0,
+                    );
                 }
                 // we allow direct float comparison since this is not comparing 2 calculated values
                 // but rather 2 previously stored floats
                 #[allow(clippy::float_cmp)]
                 if image_iter[idx] != self.config.iter as f64 {
-                    let rgb = Mandelbrot::point_color(hue);
+                    let rgb = if self.config.linear_blend {
+                        let a = self.palette.color(hues[m.floor() as usize]);
+                        let b = self.palette.color(hues[m.ceil() as usize]);
+                        Mandelbrot::blend_linear(a, b, (m % 1.0) as f32)
+                    } else {
+                        self.palette.color(t)
+                    };
                     // set the image colors
                     match self.config.pix {
                         PixelFormat::RGBA8 => {
@@ -193,29 +311,134 @@ impl Mandelbrot {
         image
     }
 
+    /// Renders the current configuration, then reduces the RGBA buffer to at
+    /// most `max_colors` entries via median-cut quantization. Returns the
+    /// palette alongside one palette index per pixel, suitable for
+    /// indexed-PNG or GIF encoding.
+    pub fn render_quantized(&mut self, max_colors: usize) -> IndexedImage {
+        let image = self.render();
+        let pix = self.config.pix;
+        let pixels: Vec<(u8, u8, u8, u8)> = image
+            .chunks_exact(4)
+            .map(|c| match pix {
+                PixelFormat::RGBA8 => (c[0], c[1], c[2], c[3]),
+                PixelFormat::BGRA8 => (c[2], c[1], c[0], c[3]),
+            })
+            .collect();
+        quantize::median_cut(&pixels, max_colors)
+    }
+
+    /// Returns an iterator that yields `frames` rendered buffers animating a
+    /// smooth zoom from the current `center`/`zoom` toward `target`/`end_zoom`.
+    /// Zoom is interpolated geometrically (`zoom *= (end_zoom/start_zoom).powf(1.0/frames)`
+    /// per frame) so perceived zoom speed stays constant; `center` is interpolated
+    /// linearly toward `target`. Each yielded buffer matches the configured
+    /// `PixelFormat` and leaves `config.center`/`config.zoom` at their final
+    /// animated values once the iterator is exhausted.
+    pub fn animate(&mut self, target: Complex64, end_zoom: f64, frames: u32) -> AnimationFrames<'_> {
+        assert!(frames > 0, "frames must be greater than 0");
+        let start_center = self.config.center;
+        let start_zoom = self.config.zoom;
+        let zoom_ratio = (end_zoom / start_zoom).powf(1.0 / frames as f64);
+        AnimationFrames {
+            mandelbrot: self,
+            target,
+            start_center,
+            start_zoom,
+            zoom_ratio,
+            frames,
+            frame: 0,
+        }
+    }
+
     fn linear_interpolation(a: f64, b: f64, t: f64) -> f32 {
         (a * (1.0 - t) + b * t) as f32
     }
 
+    /// Blends two RGB colors in linear light rather than directly in sRGB, so
+    /// gradients don't band or darken in the transitions.
+    fn blend_linear(a: (u8, u8, u8), b: (u8, u8, u8), t: f32) -> (u8, u8, u8) {
+        let blend = |a: u8, b: u8| -> u8 {
+            let la = srgb_to_linear(a as f64 / 255.0);
+            let lb = srgb_to_linear(b as f64 / 255.0);
+            let blended = Mandelbrot::linear_interpolation(la, lb, t as f64) as f64;
+            (linear_to_srgb(blended) * 255.0).round() as u8
+        };
+        (blend(a.0, b.0), blend(a.1, b.1), blend(a.2, b.2))
+    }
+
     fn calc_point(&self, c: Complex64) -> f64 {
-        let mut z: Complex64 = Complex64::new(0.0, 0.0);
+        // Julia seeds z with the pixel coordinate and iterates toward a fixed
+        // constant; every other family starts at the origin and iterates
+        // toward the pixel coordinate.
+        let (mut z, c_add) = match self.config.kind {
+            FractalKind::Julia { c: c_fixed } => (c, c_fixed),
+            _ => (Complex64::new(0.0, 0.0), c),
+        };
         let mut iter: u16 = 0;
+        // Multibrot's smoothing takes ln(ln(|z|)); bailing out as soon as |z| > 2
+        // can leave |z| < e, making that term negative and pushing the smoothed
+        // result past config.iter. A larger radius guarantees |z| > e (so
+        // ln(ln(|z|)) >= 0) whenever the loop exits.
+        let bailout_sqr = match self.config.kind {
+            FractalKind::Multibrot { .. } => 100.0 * 100.0,
+            _ => 4.0,
+        };
 
-        while (z.norm_sqr() <= 4.0) && (iter < self.config.iter) {
-            z = (z * z) + c;
+        while (z.norm_sqr() <= bailout_sqr) && (iter < self.config.iter) {
+            z = match self.config.kind {
+                FractalKind::BurningShip => {
+                    let folded = Complex64::new(z.re.abs(), z.im.abs());
+                    folded * folded + c_add
+                }
+                FractalKind::Tricorn => z.conj() * z.conj() + c_add,
+                FractalKind::Multibrot { power } => z.powu(power) + c_add,
+                FractalKind::Mandelbrot | FractalKind::Julia { .. } => (z * z) + c_add,
+            };
             iter += 1;
         }
         if iter == self.config.iter {
             return iter as f64;
         }
         let abs_z = z.norm_sqr().sqrt();
-        iter as f64 + 1.0_f64 - abs_z.log2().log10()
+        match self.config.kind {
+            FractalKind::Multibrot { power } => {
+                iter as f64 + 1.0_f64 - abs_z.ln().log(power as f64)
+            }
+            _ => iter as f64 + 1.0_f64 - abs_z.log2().log10(),
+        }
     }
+}
 
-    fn point_color(hue: f32) -> (u8, u8, u8) {
-        let lum = 0.5;
-        let sat = 0.90;
-        hsl_rgb(hue, sat, lum)
+/// Iterator returned by [`Mandelbrot::animate`]; each call to `next` advances
+/// the underlying `Mandelbrot`'s center/zoom one frame closer to the target
+/// and renders it.
+pub struct AnimationFrames<'a> {
+    mandelbrot: &'a mut Mandelbrot,
+    target: Complex64,
+    start_center: Complex64,
+    start_zoom: f64,
+    zoom_ratio: f64,
+    frames: u32,
+    frame: u32,
+}
+
+impl<'a> Iterator for AnimationFrames<'a> {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Vec<u8>> {
+        if self.frame >= self.frames {
+            return None;
+        }
+        self.frame += 1;
+        let t = self.frame as f64 / self.frames as f64;
+        self.mandelbrot.config.center = Complex64::new(
+            self.start_center.re + (self.target.re - self.start_center.re) * t,
+            self.start_center.im + (self.target.im - self.start_center.im) * t,
+        );
+        self.mandelbrot.config.zoom = self.start_zoom * self.zoom_ratio.powi(self.frame as i32);
+        self.mandelbrot.recompute_viewport();
+        Some(self.mandelbrot.render())
     }
 }
 
@@ -233,6 +456,8 @@ mod test {
             zoom: 1.0,
             iter: 720,
             pix: PixelFormat::RGBA8,
+            kind: FractalKind::Mandelbrot,
+            linear_blend: false,
         };
         let m = Mandelbrot::new(config);
         assert!(
@@ -263,6 +488,8 @@ mod test {
             zoom: 0.1,
             iter: 100,
             pix: PixelFormat::RGBA8,
+            kind: FractalKind::Mandelbrot,
+            linear_blend: false,
         };
         let mut m = Mandelbrot::new(config);
         let image = m.render();
@@ -279,4 +506,167 @@ mod test {
         assert!(image[396] < image[397], "B should be less than G");
         assert!(image[398] < image[397], "R should be less than G");
     }
+
+    /// test that non-Mandelbrot fractal kinds render and that Tricorn agrees
+    /// with Mandelbrot on the real axis, where conj(z) == z
+    #[test]
+    fn test_fractal_kind_dispatch() {
+        let mandelbrot = Config {
+            width: 50,
+            height: 50,
+            center: Complex64::new(-0.0, 0.0),
+            zoom: 0.1,
+            iter: 100,
+            pix: PixelFormat::RGBA8,
+            kind: FractalKind::Mandelbrot,
+            linear_blend: false,
+        };
+        let tricorn = Config {
+            kind: FractalKind::Tricorn,
+            ..mandelbrot
+        };
+        let m = Mandelbrot::new(mandelbrot);
+        let t = Mandelbrot::new(tricorn);
+        assert_eq!(
+            m.calc_point(Complex64::new(0.3, 0.0)),
+            t.calc_point(Complex64::new(0.3, 0.0)),
+            "Tricorn should match Mandelbrot on the real axis"
+        );
+
+        let burning_ship = Config {
+            kind: FractalKind::BurningShip,
+            ..mandelbrot
+        };
+        let mut m = Mandelbrot::new(burning_ship);
+        let image = m.render();
+        assert_eq!(10_000, image.len(), "expected len 10_000");
+    }
+
+    /// test that Multibrot renders without panicking; regression test for the
+    /// smoothed escape value exceeding config.iter and indexing hues out of bounds
+    #[test]
+    fn test_multibrot_render() {
+        let config = Config {
+            width: 50,
+            height: 50,
+            center: Complex64::new(-0.0, 0.0),
+            zoom: 0.1,
+            iter: 100,
+            pix: PixelFormat::RGBA8,
+            kind: FractalKind::Multibrot { power: 3 },
+            linear_blend: false,
+        };
+        let mut m = Mandelbrot::new(config);
+        let image = m.render();
+        assert_eq!(10_000, image.len(), "expected len 10_000");
+    }
+
+    /// test that enabling linear_blend still renders a correctly sized image
+    #[test]
+    fn test_linear_blend_render() {
+        let config = Config {
+            width: 50,
+            height: 50,
+            center: Complex64::new(-0.0, 0.0),
+            zoom: 0.1,
+            iter: 100,
+            pix: PixelFormat::RGBA8,
+            kind: FractalKind::Mandelbrot,
+            linear_blend: true,
+        };
+        let mut m = Mandelbrot::new(config);
+        let image = m.render();
+        assert_eq!(10_000, image.len(), "expected len 10_000");
+    }
+
+    /// test that render_quantized caps the palette at max_colors and returns
+    /// one index per pixel
+    #[test]
+    fn test_render_quantized() {
+        let config = Config {
+            width: 50,
+            height: 50,
+            center: Complex64::new(-0.0, 0.0),
+            zoom: 0.1,
+            iter: 100,
+            pix: PixelFormat::RGBA8,
+            kind: FractalKind::Mandelbrot,
+            linear_blend: false,
+        };
+        let mut m = Mandelbrot::new(config);
+        let (rgb_palette, indices) = m.render_quantized(16);
+        assert!(rgb_palette.len() <= 16, "palette should be capped at 16");
+        assert_eq!(2_500, indices.len(), "expected one index per pixel");
+        for &i in &indices {
+            assert!((i as usize) < rgb_palette.len());
+        }
+    }
+
+    /// test that pan shifts the viewport by the expected number of complex
+    /// units and that zoom_at keeps the targeted pixel's complex coordinate fixed
+    #[test]
+    fn test_pan_and_zoom_at() {
+        let config = Config {
+            width: 100,
+            height: 100,
+            center: Complex64::new(-0.0, 0.0),
+            zoom: 1.0,
+            iter: 100,
+            pix: PixelFormat::RGBA8,
+            kind: FractalKind::Mandelbrot,
+            linear_blend: false,
+        };
+        let mut m = Mandelbrot::new(config);
+        let (x_step, y_step) = m.pixel_steps();
+        m.pan(10.0, 0.0);
+        assert!(
+            (m.config.center.re - (10.0 * x_step)).abs() < 1e-12,
+            "pan should shift center.re by dx_pixels * x_step"
+        );
+        assert!(
+            (m.config.center.im).abs() < 1e-12,
+            "pan along x should not move center.im"
+        );
+        let _ = y_step;
+
+        let mut m = Mandelbrot::new(config);
+        let before = m.pixel_to_complex(25, 25);
+        m.zoom_at(25, 25, 2.0);
+        let after = m.pixel_to_complex(25, 25);
+        assert!(
+            (before.re - after.re).abs() < 1e-9 && (before.im - after.im).abs() < 1e-9,
+            "zoom_at should keep the targeted pixel's complex coordinate fixed"
+        );
+    }
+
+    /// test that animate yields exactly `frames` correctly sized buffers and
+    /// ends at the requested zoom level
+    #[test]
+    fn test_animate() {
+        let config = Config {
+            width: 20,
+            height: 20,
+            center: Complex64::new(-0.0, 0.0),
+            zoom: 1.0,
+            iter: 50,
+            pix: PixelFormat::RGBA8,
+            kind: FractalKind::Mandelbrot,
+            linear_blend: false,
+        };
+        let mut m = Mandelbrot::new(config);
+        let target = Complex64::new(-0.5, 0.0);
+        let frames: Vec<Vec<u8>> = m.animate(target, 4.0, 5).collect();
+        assert_eq!(5, frames.len(), "expected 5 animation frames");
+        for frame in &frames {
+            assert_eq!(1_600, frame.len(), "expected len 1_600 per frame");
+        }
+        assert!(
+            (m.config.zoom - 4.0).abs() < 1e-9,
+            "zoom should land on end_zoom after the last frame"
+        );
+        assert!(
+            (m.config.center.re - target.re).abs() < 1e-9,
+            "center should land on target after the last frame"
+        );
+    }
 }
@@ -0,0 +1,109 @@
+use crate::util::hsl_rgb;
+
+/// Maps a normalized histogram value (`0.0..=1.0`) to an RGB color. Implementations
+/// are plugged into [`crate::Mandelbrot`] via `set_palette` so pass 2 of `render`
+/// no longer has to hard-code a single look. Requires `Sync + Send` since pass 1
+/// samples pixels in parallel across a shared `&Mandelbrot`.
+pub trait Palette: Sync + Send {
+    fn color(&self, t: f32) -> (u8, u8, u8);
+}
+
+/// The original HSL sweep: hue runs from 360 down to 0 as `t` runs from 0.0 to 1.0,
+/// at a fixed saturation and luminosity.
+pub struct HslPalette {
+    pub sat: f32,
+    pub lum: f32,
+}
+
+impl Default for HslPalette {
+    fn default() -> Self {
+        HslPalette {
+            sat: 0.90,
+            lum: 0.5,
+        }
+    }
+}
+
+impl Palette for HslPalette {
+    fn color(&self, t: f32) -> (u8, u8, u8) {
+        let hue = 360.0 - t * 360.0;
+        hsl_rgb(hue, self.sat, self.lum)
+    }
+}
+
+/// A discrete gradient built from user-supplied RGB stops, linearly interpolated
+/// between the two stops neighbouring `t`.
+pub struct GradientPalette {
+    stops: Vec<(u8, u8, u8)>,
+}
+
+impl GradientPalette {
+    /// Creates a gradient palette from at least two RGB stops, evenly spaced
+    /// across the `0.0..=1.0` range.
+    pub fn new(stops: Vec<(u8, u8, u8)>) -> GradientPalette {
+        assert!(
+            stops.len() >= 2,
+            "gradient palette requires at least 2 stops"
+        );
+        GradientPalette { stops }
+    }
+}
+
+impl Palette for GradientPalette {
+    fn color(&self, t: f32) -> (u8, u8, u8) {
+        let t = t.clamp(0.0, 1.0);
+        let segments = (self.stops.len() - 1) as f32;
+        let pos = t * segments;
+        let idx = (pos.floor() as usize).min(self.stops.len() - 2);
+        let frac = pos - idx as f32;
+        let a = self.stops[idx];
+        let b = self.stops[idx + 1];
+        (
+            (a.0 as f32 + (b.0 as f32 - a.0 as f32) * frac) as u8,
+            (a.1 as f32 + (b.1 as f32 - a.1 as f32) * frac) as u8,
+            (a.2 as f32 + (b.2 as f32 - a.2 as f32) * frac) as u8,
+        )
+    }
+}
+
+/// Wraps another palette and repeats it over `period` cycles across the
+/// `0.0..=1.0` histogram range, producing a cyclic/banded look.
+pub struct BandedPalette {
+    inner: Box<dyn Palette + Sync + Send>,
+    period: f32,
+}
+
+impl BandedPalette {
+    pub fn new(inner: Box<dyn Palette + Sync + Send>, period: f32) -> BandedPalette {
+        BandedPalette { inner, period }
+    }
+}
+
+impl Palette for BandedPalette {
+    fn color(&self, t: f32) -> (u8, u8, u8) {
+        let banded = (t * self.period) % 1.0;
+        self.inner.color(banded)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// test gradient interpolation between stops
+    #[test]
+    fn test_gradient_palette() {
+        let gradient = GradientPalette::new(vec![(0, 0, 0), (255, 255, 255)]);
+        assert_eq!((0, 0, 0), gradient.color(0.0));
+        assert_eq!((255, 255, 255), gradient.color(1.0));
+        let mid = gradient.color(0.5);
+        assert!(mid.0 > 100 && mid.0 < 155, "midpoint should be mid-gray");
+    }
+
+    /// test banded palette wraps the inner palette across `period` cycles
+    #[test]
+    fn test_banded_palette() {
+        let banded = BandedPalette::new(Box::new(GradientPalette::new(vec![(0, 0, 0), (255, 255, 255)])), 2.0);
+        assert_eq!(banded.color(0.0), banded.color(0.5));
+    }
+}
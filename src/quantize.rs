@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+/// A quantized image: an RGBA palette (at most 256 entries) paired with one
+/// palette index per pixel.
+pub type IndexedImage = (Vec<(u8, u8, u8, u8)>, Vec<u8>);
+
+/// One box in the median-cut tree: a set of unique pixel colors (with their
+/// occurrence counts) spanning an axis-aligned RGB volume.
+struct ColorBox {
+    colors: Vec<((u8, u8, u8, u8), u32)>,
+}
+
+impl ColorBox {
+    fn channel_range(&self, channel: usize) -> (u8, u8) {
+        let mut lo = u8::MAX;
+        let mut hi = 0u8;
+        for (c, _) in &self.colors {
+            let v = match channel {
+                0 => c.0,
+                1 => c.1,
+                _ => c.2,
+            };
+            lo = lo.min(v);
+            hi = hi.max(v);
+        }
+        (lo, hi)
+    }
+
+    fn widest_channel(&self) -> usize {
+        (0..3)
+            .max_by_key(|&ch| {
+                let (lo, hi) = self.channel_range(ch);
+                hi - lo
+            })
+            .unwrap_or(0)
+    }
+
+    /// Splits this box in two at the population-weighted median along its
+    /// widest channel.
+    fn split(mut self) -> (ColorBox, ColorBox) {
+        let channel = self.widest_channel();
+        self.colors.sort_by_key(|(c, _)| match channel {
+            0 => c.0,
+            1 => c.1,
+            _ => c.2,
+        });
+        let total: u32 = self.colors.iter().map(|(_, n)| n).sum();
+        let mut running = 0u32;
+        let mut split_at = 1;
+        for (i, (_, n)) in self.colors.iter().enumerate() {
+            running += n;
+            if running * 2 >= total {
+                split_at = (i + 1).clamp(1, self.colors.len() - 1);
+                break;
+            }
+        }
+        let right = self.colors.split_off(split_at);
+        (ColorBox { colors: self.colors }, ColorBox { colors: right })
+    }
+
+    fn average(&self) -> (u8, u8, u8, u8) {
+        let total: u64 = self.colors.iter().map(|(_, n)| *n as u64).sum();
+        let mut sum = (0u64, 0u64, 0u64, 0u64);
+        for (c, n) in &self.colors {
+            let n = *n as u64;
+            sum.0 += c.0 as u64 * n;
+            sum.1 += c.1 as u64 * n;
+            sum.2 += c.2 as u64 * n;
+            sum.3 += c.3 as u64 * n;
+        }
+        (
+            (sum.0 / total) as u8,
+            (sum.1 / total) as u8,
+            (sum.2 / total) as u8,
+            (sum.3 / total) as u8,
+        )
+    }
+}
+
+/// Reduces an RGBA buffer to at most `max_colors` palette entries using
+/// median-cut quantization: start with one box spanning every unique pixel,
+/// repeatedly split the box with the largest channel range at its
+/// population-weighted median until `max_colors` boxes exist (or no box can
+/// be split further), then average each box into a palette entry and map
+/// every pixel to its nearest entry.
+pub fn median_cut(pixels: &[(u8, u8, u8, u8)], max_colors: usize) -> IndexedImage {
+    assert!(
+        max_colors > 0 && max_colors <= 256,
+        "max_colors must be in 1..=256"
+    );
+    assert!(!pixels.is_empty(), "pixels must not be empty");
+
+    let mut counts: HashMap<(u8, u8, u8, u8), u32> = HashMap::new();
+    for &p in pixels {
+        *counts.entry(p).or_insert(0) += 1;
+    }
+    let mut boxes = vec![ColorBox {
+        colors: counts.into_iter().collect(),
+    }];
+
+    while boxes.len() < max_colors {
+        let split_idx = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.colors.len() > 1)
+            .max_by_key(|(_, b)| {
+                (0..3)
+                    .map(|ch| {
+                        let (lo, hi) = b.channel_range(ch);
+                        hi - lo
+                    })
+                    .max()
+                    .unwrap_or(0)
+            })
+            .map(|(i, _)| i);
+        let idx = match split_idx {
+            Some(i) => i,
+            None => break,
+        };
+        let box_to_split = boxes.remove(idx);
+        let (a, b) = box_to_split.split();
+        boxes.push(a);
+        boxes.push(b);
+    }
+
+    let palette: Vec<(u8, u8, u8, u8)> = boxes.iter().map(ColorBox::average).collect();
+    let indices = pixels.iter().map(|&p| nearest_entry(&palette, p)).collect();
+    (palette, indices)
+}
+
+fn nearest_entry(palette: &[(u8, u8, u8, u8)], pixel: (u8, u8, u8, u8)) -> u8 {
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, c)| {
+            let dr = pixel.0 as i32 - c.0 as i32;
+            let dg = pixel.1 as i32 - c.1 as i32;
+            let db = pixel.2 as i32 - c.2 as i32;
+            let da = pixel.3 as i32 - c.3 as i32;
+            dr * dr + dg * dg + db * db + da * da
+        })
+        .map(|(i, _)| i as u8)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// test that quantizing down to 2 colors separates black from white
+    #[test]
+    fn test_median_cut_basic() {
+        let pixels = vec![
+            (0, 0, 0, 255),
+            (0, 0, 0, 255),
+            (10, 10, 10, 255),
+            (255, 255, 255, 255),
+            (245, 245, 245, 255),
+        ];
+        let (palette, indices) = median_cut(&pixels, 2);
+        assert_eq!(2, palette.len(), "expected 2 palette entries");
+        assert_eq!(pixels.len(), indices.len());
+        // the two black-ish pixels should map to the same entry, distinct
+        // from the two white-ish pixels
+        assert_eq!(indices[0], indices[1]);
+        assert_ne!(indices[0], indices[3]);
+        assert_eq!(indices[3], indices[4]);
+    }
+
+    /// test that requesting more colors than unique pixels doesn't panic and
+    /// simply yields one entry per unique color
+    #[test]
+    fn test_median_cut_fewer_unique_than_max() {
+        let pixels = vec![(1, 2, 3, 255), (1, 2, 3, 255), (4, 5, 6, 255)];
+        let (palette, indices) = median_cut(&pixels, 16);
+        assert_eq!(2, palette.len());
+        assert_eq!(3, indices.len());
+    }
+
+    /// test that an empty pixel slice is rejected rather than panicking on a
+    /// divide-by-zero average
+    #[test]
+    #[should_panic(expected = "pixels must not be empty")]
+    fn test_median_cut_empty_pixels_panics() {
+        let pixels: Vec<(u8, u8, u8, u8)> = vec![];
+        median_cut(&pixels, 16);
+    }
+}
@@ -1,3 +1,23 @@
+/// Converts an 8-bit sRGB channel value (0.0..=1.0, i.e. `byte as f64 / 255.0`)
+/// to linear light, so it can be blended without the sRGB gamma curve
+/// darkening the transitions.
+pub fn srgb_to_linear(c: f64) -> f64 {
+    if c > 0.04045 {
+        ((c + 0.055) / 1.055).powf(2.4)
+    } else {
+        c / 12.92
+    }
+}
+
+/// Converts a linear-light channel value (0.0..=1.0) back to sRGB.
+pub fn linear_to_srgb(c: f64) -> f64 {
+    if c > 0.0031308 {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    } else {
+        c * 12.92
+    }
+}
+
 /// Converts HSL values to RGB values.
 ///
 /// # Arguments
@@ -78,4 +98,15 @@ mod test {
         assert_eq!(143, result.1);
         assert_eq!(239, result.2);
     }
+
+    /// test sRGB <-> linear light round trip and known endpoints
+    #[test]
+    fn test_srgb_linear_round_trip() {
+        assert_eq!(0.0, srgb_to_linear(0.0));
+        assert!((1.0 - srgb_to_linear(1.0)).abs() < 1e-9);
+        assert_eq!(0.0, linear_to_srgb(0.0));
+        assert!((1.0 - linear_to_srgb(1.0)).abs() < 1e-9);
+        let c = 0.6;
+        assert!((c - linear_to_srgb(srgb_to_linear(c))).abs() < 1e-9);
+    }
 }